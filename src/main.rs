@@ -1,29 +1,373 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::sync::mpsc::{self, Sender};
 use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 
 use chrono::{DateTime, TimeZone, Timelike, Utc};
 use futures::executor::block_on;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
 use windows::core::HRESULT;
 use windows::{
     core::{Error, Result},
-    Foundation::{EventRegistrationToken, TypedEventHandler},
+    ApplicationModel::AppDisplayInfo,
+    Foundation::{EventRegistrationToken, Size, TypedEventHandler},
+    Graphics::Imaging::{
+        BitmapAlphaMode, BitmapDecoder, BitmapPixelFormat, BitmapTransform, ColorManagementMode,
+        ExifOrientationMode,
+    },
     UI::Notifications::{
         KnownNotificationBindings,
         Management::{UserNotificationListener, UserNotificationListenerAccessStatus},
         UserNotificationChangedEventArgs, UserNotificationChangedKind,
     },
 };
-use winsafe::co::SS;
-use winsafe::{co, gui, prelude::*, AnyResult, ExitThread, HWND};
+use winsafe::{
+    co, gui, prelude::*, AnyResult, ExitThread, BITMAPINFO, BITMAPINFOHEADER, HIMAGELIST, HWND,
+    SIZE,
+};
 
 const DEFAULT_HEIGHT: u32 = 150;
 const DEFAULT_WIDTH: u32 = 300;
 
+/// Custom message posted from the (off-thread) COM callback to ask the UI
+/// thread to re-read the shared model and refresh the list.
+const WM_NOTIFICATION_UPDATE: co::WM = unsafe { co::WM::from_raw(co::WM::APP.raw() + 1) };
+
+/// Side length, in pixels, used for extracted app logos.
+const LOGO_SIZE: u32 = 32;
+
+/// JSON body posted to the relay endpoint for a single notification.
+#[derive(Serialize)]
+struct RelayMessage {
+    app: String,
+    text: String,
+    /// ISO-8601 / RFC 3339 timestamp.
+    timestamp: String,
+}
+
+/// Sender half of the relay queue; moved into the COM callback so sending is
+/// a non-blocking handoff to the background worker thread.
+type RelaySender = Sender<RelayMessage>;
+
+/// Maximum delivery attempts before a message is dropped.
+const RELAY_MAX_ATTEMPTS: u32 = 3;
+/// Base pause after a failed attempt; doubled per attempt (exponential
+/// backoff) so a persistently unreachable endpoint backs off instead of
+/// hammering, while the first retry is still quick.
+const RELAY_RETRY_BASE_PAUSE: Duration = Duration::from_millis(250);
+
+/// Spawn the background worker draining the relay queue and return both the
+/// sender used to enqueue messages and the worker's join handle (so it can be
+/// shut down cleanly — dropping the sender closes the channel and ends the
+/// loop).
+fn start_relay(config: &RelayConfig) -> (RelaySender, std::thread::JoinHandle<()>) {
+    let (tx, rx) = mpsc::channel::<RelayMessage>();
+    let url = config.url.clone();
+    let worker = std::thread::spawn(move || {
+        // Retries are requeued behind newer messages rather than retried in
+        // place, so newer messages get their turn first. The backoff pause
+        // after a failure still runs on this thread and delays the whole queue.
+        let mut pending: VecDeque<(RelayMessage, u32)> = VecDeque::new();
+        loop {
+            if pending.is_empty() {
+                match rx.recv() {
+                    Ok(message) => pending.push_back((message, 0)),
+                    Err(_) => break, // sender dropped -> shut down
+                }
+            }
+            // Absorb everything already queued before working the front.
+            while let Ok(message) = rx.try_recv() {
+                pending.push_back((message, 0));
+            }
+
+            let (message, attempts) = match pending.pop_front() {
+                Some(item) => item,
+                None => continue,
+            };
+            match ureq::post(&url).send_json(&message) {
+                Ok(_) => {}
+                Err(e) => {
+                    let attempts = attempts + 1;
+                    if attempts < RELAY_MAX_ATTEMPTS {
+                        println!("Warning: relay attempt {} failed, requeueing: {}", attempts, e);
+                        pending.push_back((message, attempts));
+                        // Exponential backoff keyed on the attempt count:
+                        // 250ms, 500ms, ... so repeated failures ease off.
+                        std::thread::sleep(RELAY_RETRY_BASE_PAUSE * 2u32.pow(attempts - 1));
+                    } else {
+                        println!("Warning: dropping notification after {} relay attempts: {}", RELAY_MAX_ATTEMPTS, e);
+                    }
+                }
+            }
+        }
+    });
+    (tx, worker)
+}
+
+/// A single notification as tracked by the in-memory model.
+#[derive(Clone)]
+struct NotificationRecord {
+    app_name: String,
+    text: String,
+    time: DateTime<Utc>,
+    id: u32,
+    /// Decoded 32x32 BGRA8 logo pixels, shared with the logo cache.
+    logo: Option<Arc<Vec<u8>>>,
+}
+
+/// Shared, append-only-then-pruned history of live notifications.
+type NotificationModel = Arc<Mutex<Vec<NotificationRecord>>>;
+
+/// Decoded logos keyed on app display name so a stream is read at most once
+/// per app.
+type LogoCache = Arc<Mutex<HashMap<String, Option<Arc<Vec<u8>>>>>>;
+
+/// Read and decode an app's logo into `LOGO_SIZE`-square BGRA8 pixels.
+///
+/// The logo is exposed as an `IRandomAccessStreamReference`; we open it,
+/// decode it, and ask for pre-scaled, premultiplied BGRA pixels so the bytes
+/// can be copied straight into the 32bpp DIB section built by [`add_logo`].
+async fn fetch_logo_pixels(app_display_info: &AppDisplayInfo) -> Result<Vec<u8>> {
+    let stream_ref = app_display_info.GetLogo(Size {
+        Width: LOGO_SIZE as f32,
+        Height: LOGO_SIZE as f32,
+    })?;
+    let stream = stream_ref.OpenReadAsync()?.await?;
+    let decoder = BitmapDecoder::CreateAsync(&stream)?.await?;
+
+    let transform = BitmapTransform::new()?;
+    transform.SetScaledWidth(LOGO_SIZE)?;
+    transform.SetScaledHeight(LOGO_SIZE)?;
+
+    let pixel_data = decoder
+        .GetPixelDataTransformedAsync(
+            BitmapPixelFormat::Bgra8,
+            BitmapAlphaMode::Premultiplied,
+            &transform,
+            ExifOrientationMode::IgnoreExifOrientation,
+            ColorManagementMode::DoNotColorManage,
+        )?
+        .await?;
+
+    Ok(pixel_data.DetachPixelData()?.to_vec())
+}
+
+/// Turn decoded BGRA8 pixels into an `HBITMAP` and append it to `image_list`,
+/// returning the index to reference it from a list item.
+fn add_logo(image_list: &HIMAGELIST, pixels: &[u8]) -> Option<u32> {
+    // A device-dependent `CreateBitmap` drops the alpha channel, so an
+    // `ILC::COLOR32` list blends transparent regions as black. Build a
+    // top-down 32bpp DIB section and copy the premultiplied BGRA bytes in so
+    // the alpha survives.
+    let screen_dc = HWND::NULL.GetDC().ok()?;
+
+    let info = BITMAPINFO {
+        bmiHeader: BITMAPINFOHEADER {
+            biWidth: LOGO_SIZE as i32,
+            biHeight: -(LOGO_SIZE as i32), // negative => top-down rows
+            biPlanes: 1,
+            biBitCount: 32,
+            biCompression: co::BI::RGB,
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+
+    let (bitmap, bits) = screen_dc
+        .CreateDIBSection(&info, co::DIB::RGB_COLORS, None, 0)
+        .ok()?;
+
+    let expected = (LOGO_SIZE * LOGO_SIZE * 4) as usize;
+    // SAFETY: `bits` points at `expected` bytes of DIB storage, and `pixels`
+    // holds LOGO_SIZE*LOGO_SIZE 32bpp BGRA samples; copy the overlap.
+    unsafe {
+        std::ptr::copy_nonoverlapping(
+            pixels.as_ptr(),
+            bits as *mut u8,
+            pixels.len().min(expected),
+        );
+    }
+
+    image_list.Add(&bitmap, None).ok()
+}
+
+/// Return the cached logo for `app_name`, decoding and caching it on first use.
+/// Logo failures are non-fatal: `None` simply means "no icon for this row".
+fn logo_for_app(
+    cache: &LogoCache,
+    app_name: &str,
+    app_display_info: &AppDisplayInfo,
+) -> Option<Arc<Vec<u8>>> {
+    // A cached entry may be a negative result (`None`): an app whose logo
+    // failed to load is remembered so the blocking stream read isn't retried
+    // on every toast.
+    if let Some(logo) = cache.lock().unwrap().get(app_name) {
+        return logo.clone();
+    }
+    let logo = match block_on(fetch_logo_pixels(app_display_info)) {
+        Ok(pixels) => Some(Arc::new(pixels)),
+        Err(e) => {
+            println!("Warning: could not load logo for {}: {}", app_name, e);
+            None
+        }
+    };
+    cache
+        .lock()
+        .unwrap()
+        .insert(app_name.to_owned(), logo.clone());
+    logo
+}
+
+/// What to do with a given app's notifications.
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+enum AppAction {
+    #[default]
+    Allow,
+    Block,
+}
+
+/// Per-app override: a base allow/block decision plus optional text filters
+/// that suppress individual toasts whose body matches.
+#[derive(Clone, Debug, Default, Deserialize)]
+struct AppRule {
+    #[serde(default)]
+    action: AppAction,
+    /// Case-insensitive substrings; a toast containing any of them is dropped.
+    #[serde(default)]
+    keywords: Vec<String>,
+    /// Optional regular expression; a toast whose text matches is dropped.
+    #[serde(default)]
+    regex: Option<String>,
+    /// Pattern from `regex`, compiled once at load time. Never deserialized
+    /// directly — populated by [`Config::load`].
+    #[serde(skip)]
+    compiled_regex: Option<Regex>,
+}
+
+/// Optional HTTP relay: mirror each surfaced notification to an endpoint.
+#[derive(Clone, Debug, Deserialize)]
+struct RelayConfig {
+    /// Off by default; set to `true` to enable forwarding.
+    #[serde(default)]
+    enabled: bool,
+    /// Endpoint that receives a JSON POST per notification. Defaults to empty
+    /// so a `relay` block without a `url` can't reject the whole config; an empty
+    /// URL simply leaves the relay disabled (see [`RelayConfig::is_active`]).
+    #[serde(default)]
+    url: String,
+}
+
+impl RelayConfig {
+    /// Whether forwarding should actually run: explicitly enabled and pointed
+    /// at a non-empty endpoint.
+    fn is_active(&self) -> bool {
+        self.enabled && !self.url.trim().is_empty()
+    }
+}
+
+/// User-tunable notification filtering, loaded from `%APPDATA%\skal\config.json`.
+#[derive(Clone, Debug, Deserialize)]
+struct Config {
+    /// Master switch; when `false` nothing is surfaced.
+    #[serde(default = "default_enabled")]
+    enabled: bool,
+    /// Overrides keyed on `AppInfo().DisplayInfo().DisplayName()`.
+    #[serde(default)]
+    apps: HashMap<String, AppRule>,
+    /// Optional outbound relay; disabled unless present and enabled.
+    #[serde(default)]
+    relay: Option<RelayConfig>,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            apps: HashMap::new(),
+            relay: None,
+        }
+    }
+}
+
+impl Config {
+    /// Load the config from `%APPDATA%\skal\config.json`, falling back to a
+    /// permissive default when the file is absent or unreadable.
+    fn load() -> Self {
+        let path = match std::env::var("APPDATA") {
+            Ok(appdata) => format!("{}\\skal\\config.json", appdata),
+            Err(_) => return Self::default(),
+        };
+        let contents = match std::fs::read_to_string(&path) {
+            Ok(c) => c,
+            Err(_) => return Self::default(),
+        };
+        match serde_json::from_str::<Config>(&contents) {
+            Ok(mut config) => {
+                config.compile_regexes();
+                config
+            }
+            Err(e) => {
+                println!("Warning: ignoring invalid config at {}: {}", path, e);
+                Self::default()
+            }
+        }
+    }
+
+    /// Compile each app rule's regex once, surfacing invalid patterns here
+    /// rather than on every incoming notification.
+    fn compile_regexes(&mut self) {
+        for (app_name, rule) in self.apps.iter_mut() {
+            if let Some(pattern) = &rule.regex {
+                match Regex::new(pattern) {
+                    Ok(re) => rule.compiled_regex = Some(re),
+                    Err(e) => println!("Warning: invalid regex for {}: {}", app_name, e),
+                }
+            }
+        }
+    }
+
+    /// Decide whether a resolved notification should reach the model.
+    fn should_surface(&self, app_name: &str, text: &str) -> bool {
+        if !self.enabled {
+            return false;
+        }
+        let rule = match self.apps.get(app_name) {
+            Some(rule) => rule,
+            None => return true,
+        };
+        if rule.action == AppAction::Block {
+            return false;
+        }
+        let lower = text.to_lowercase();
+        if rule
+            .keywords
+            .iter()
+            .any(|kw| lower.contains(&kw.to_lowercase()))
+        {
+            return false;
+        }
+        if let Some(re) = &rule.compiled_regex {
+            if re.is_match(text) {
+                return false;
+            }
+        }
+        true
+    }
+}
+
 #[derive(Clone)]
 struct MainWindow {
     wnd: gui::WindowMain,
-    txt: gui::Label,
+    list: gui::ListView,
+    model: NotificationModel,
     token_ptr: Arc<Mutex<Option<TokenContainer>>>,
 }
 
@@ -34,19 +378,24 @@ impl MainWindow {
             size: (DEFAULT_WIDTH, DEFAULT_HEIGHT),
             ..Default::default()
         });
-        let txt = gui::Label::new(
+        let list = gui::ListView::new(
             &wnd,
-            gui::LabelOpts {
-                text: "Waiting for notifications...".to_owned(),
+            gui::ListViewOpts {
                 position: (10, 10),
                 size: (DEFAULT_WIDTH - 20, DEFAULT_HEIGHT - 20),
-                label_style: SS::LEFT,
+                columns: vec![
+                    ("App".to_owned(), 80),
+                    ("Time".to_owned(), 60),
+                    ("Message".to_owned(), 140),
+                ],
+                list_view_style: co::LVS::REPORT | co::LVS::SINGLESEL | co::LVS::SHOWSELALWAYS,
                 ..Default::default()
             },
         );
         let new_self = Self {
             wnd,
-            txt,
+            list,
+            model: Arc::new(Mutex::new(Vec::new())),
             token_ptr: Arc::new(Mutex::new(None)),
         };
         new_self.events();
@@ -73,7 +422,11 @@ impl MainWindow {
                 }
                 Err(e) => error_dialog_and_quit(Box::new(e)),
             };
-            match setup_listener() {
+            // The callback fires off-thread, so hand it the model to mutate
+            // and the raw window handle to post a refresh back onto the UI
+            // thread once a record has been added or removed.
+            let hwnd_raw = self_2.wnd.hwnd().ptr() as isize;
+            match setup_listener(self_2.model.clone(), hwnd_raw) {
                 Ok(token) => {
                     let mut token_ptr = self_2.token_ptr.lock().unwrap();
                     *token_ptr = Some(token);
@@ -82,15 +435,61 @@ impl MainWindow {
             }
             Ok(0)
         });
+
+        let self_3 = self.clone();
+        self.wnd.on().wm(WM_NOTIFICATION_UPDATE, move |_| {
+            self_3.refresh_list();
+            Ok(Some(0))
+        });
     }
 
-    // fn update_txt(&self, new_txt: String) {
-    //     &self.txt.set_text(&new_txt);
-    // }
+    /// Rebuild the list view from the current contents of the model. Runs on
+    /// the UI thread in response to [`WM_NOTIFICATION_UPDATE`].
+    fn refresh_list(&self) {
+        let records = self.model.lock().unwrap().clone();
+
+        // Rebuild a small-icon image list from the decoded logos and hand it
+        // to the list view. `set_image_list` returns the previously-assigned
+        // list, which the control does *not* free — destroy it ourselves so
+        // the handle doesn't leak on every update.
+        let image_list = HIMAGELIST::Create(
+            SIZE::new(LOGO_SIZE as i32, LOGO_SIZE as i32),
+            co::ILC::COLOR32,
+            records.len() as i32,
+            1,
+        )
+        .ok();
+
+        let items = self.list.items();
+        items.delete_all();
+        for record in &records {
+            let (is_pm, hour) = record.time.hour12();
+            let stamp = format!(
+                "{:02}:{:02} {}",
+                hour,
+                record.time.minute(),
+                if is_pm { "PM" } else { "AM" }
+            );
+
+            let icon = match (&image_list, &record.logo) {
+                (Some(list), Some(pixels)) => add_logo(list, pixels),
+                _ => None,
+            };
+            items.add(&[&record.app_name, &stamp, &record.text], icon, ());
+        }
+
+        if let Some(list) = image_list {
+            if let Some(prev) = self.list.set_image_list(co::LVSIL::SMALL, list) {
+                let _ = prev.Destroy();
+            }
+        }
+    }
 }
 
 struct TokenContainer {
     token: EventRegistrationToken,
+    /// Join handle for the relay worker, if the relay is enabled.
+    relay_worker: Option<std::thread::JoinHandle<()>>,
 }
 
 impl Drop for TokenContainer {
@@ -98,14 +497,124 @@ impl Drop for TokenContainer {
         UserNotificationListener::Current()
             .unwrap()
             .RemoveNotificationChanged(self.token)
-            .unwrap()
+            .unwrap();
+        // Removing the handler drops the last relay sender, closing the
+        // channel; wait for the worker to finish draining and exit.
+        if let Some(worker) = self.relay_worker.take() {
+            let _ = worker.join();
+        }
+    }
+}
+
+/// Token-bucket throttle sitting in front of the per-notification logic.
+///
+/// `tokens` refill continuously at `rate` tokens per millisecond up to
+/// `max_tokens`; each admitted notification spends one token. Bursts that
+/// outrun the refill are dropped and tallied in `dropped` so the caller can
+/// surface a "+N more" summary instead of spamming the log.
+struct RateLimit {
+    max_tokens: f64,
+    tokens: f64,
+    rate: f64,
+    last_refill: Instant,
+    dropped: u32,
+}
+
+impl RateLimit {
+    fn new(max_tokens: f64, rate: f64) -> Self {
+        Self {
+            max_tokens,
+            tokens: max_tokens,
+            rate,
+            last_refill: Instant::now(),
+            dropped: 0,
+        }
+    }
+
+    /// Refill according to elapsed time and try to spend a token. Returns
+    /// `true` when the notification may be processed, `false` when it should
+    /// be dropped.
+    fn allow(&mut self) -> bool {
+        let now = Instant::now();
+        let elapsed_ms = now.duration_since(self.last_refill).as_secs_f64() * 1_000.0;
+        self.tokens = self.max_tokens.min(self.tokens + elapsed_ms * self.rate);
+        self.last_refill = now;
+
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            true
+        } else {
+            self.dropped += 1;
+            false
+        }
+    }
+}
+
+/// Largest number of notification IDs to remember for dedup purposes. Windows
+/// re-fires `Added` for the same ID, so we keep a bounded window of IDs and
+/// evict the oldest once it fills — notifications dismissed without a `Removed`
+/// event therefore can't leak for the process lifetime.
+const SEEN_CAPACITY: usize = 1024;
+
+/// Bounded, insertion-ordered set of already-surfaced `UserNotificationId`s.
+struct SeenSet {
+    ids: HashSet<u32>,
+    order: VecDeque<u32>,
+}
+
+impl SeenSet {
+    fn new() -> Self {
+        Self {
+            ids: HashSet::new(),
+            order: VecDeque::new(),
+        }
+    }
+
+    fn contains(&self, id: u32) -> bool {
+        self.ids.contains(&id)
+    }
+
+    /// Remember `id`, evicting the oldest entry if the window is full.
+    fn insert(&mut self, id: u32) {
+        if self.ids.insert(id) {
+            self.order.push_back(id);
+            if self.order.len() > SEEN_CAPACITY {
+                if let Some(oldest) = self.order.pop_front() {
+                    self.ids.remove(&oldest);
+                }
+            }
+        }
+    }
+
+    fn remove(&mut self, id: u32) {
+        if self.ids.remove(&id) {
+            self.order.retain(|&x| x != id);
+        }
     }
 }
 
 fn notification_handler(
     sender: &Option<UserNotificationListener>,
     args: &Option<UserNotificationChangedEventArgs>,
+    rate_limit: &Arc<Mutex<RateLimit>>,
+    seen: &Arc<Mutex<SeenSet>>,
+    model: &NotificationModel,
+    logos: &LogoCache,
+    config: &Arc<Config>,
+    relay: &Option<RelaySender>,
+    hwnd_raw: isize,
 ) -> Result<()> {
+    // Reconstruct the window handle so we can marshal a refresh back onto the
+    // UI thread; `PostMessage` is safe to call from any thread.
+    let hwnd = unsafe { HWND::from_ptr(hwnd_raw as *mut _) };
+    let post_update = || {
+        let _ = hwnd.PostMessage(winsafe::msg::WndMsg {
+            msg_id: WM_NOTIFICATION_UPDATE,
+            wparam: 0,
+            lparam: 0,
+        });
+    };
+
     let (listener, a) = match (sender, args) {
         (Some(listener), Some(a)) => (listener, a),
         _ => {
@@ -114,9 +623,16 @@ fn notification_handler(
         }
     };
 
+    let notification_id = a.UserNotificationId()?;
+
     match a.ChangeKind() {
         Ok(UserNotificationChangedKind::Removed) => {
-            println!("Warning: notification was removed");
+            // Forget the ID so a later re-add of the same notification is
+            // treated as fresh rather than a duplicate, and drop it from the
+            // model so the displayed history mirrors the Action Center.
+            seen.lock().unwrap().remove(notification_id);
+            model.lock().unwrap().retain(|r| r.id != notification_id);
+            post_update();
             return Ok(());
         }
         Ok(UserNotificationChangedKind::Added) => (),
@@ -126,7 +642,15 @@ fn notification_handler(
         }
     }
 
-    let notification = match listener.GetNotification(a.UserNotificationId()?) {
+    // `NotificationChanged` can fire repeatedly for the same ID; skip IDs we
+    // have already surfaced. The ID is not recorded here — only once the
+    // notification is actually surfaced below — so a re-fire of a throttled
+    // notification still gets a fresh chance rather than being swallowed.
+    if seen.lock().unwrap().contains(notification_id) {
+        return Ok(());
+    }
+
+    let notification = match listener.GetNotification(notification_id) {
         Ok(n) => n,
         _ => {
             println!("Error: could not resolve notification");
@@ -145,7 +669,6 @@ fn notification_handler(
         Utc.timestamp_opt(notification.CreationTime()?.UniversalTime, 0)
             .unwrap(),
     );
-    let (is_pm, hour) = time.hour12();
 
     let binding_type = KnownNotificationBindings::ToastGeneric()?;
     let text = notification
@@ -161,14 +684,51 @@ fn notification_handler(
         })
         .collect::<String>();
 
-    println!(
-        "{}, at {:02}:{:02} {}: {}",
+    // Honour the user's allow/block configuration before spending a token or
+    // recording anything, so chatty blocked apps can't drain the bucket.
+    if !config.should_surface(&app_name, &text) {
+        return Ok(());
+    }
+
+    // Throttle bursts. Only notifications that pass the dedup and config gates
+    // spend a token; a dropped one is coalesced into a running "+N more" tally
+    // that is flushed the next time one gets through.
+    {
+        let mut rate_limit = rate_limit.lock().unwrap();
+        if !rate_limit.allow() {
+            return Ok(());
+        }
+        if rate_limit.dropped > 0 {
+            println!("... (+{} more notifications dropped)", rate_limit.dropped);
+            rate_limit.dropped = 0;
+        }
+    }
+
+    // Only now that the notification has actually been admitted and surfaced do
+    // we remember its ID, so throttled re-fires aren't lost.
+    seen.lock().unwrap().insert(notification_id);
+
+    let logo = logo_for_app(logos, &app_name, &app_display_info);
+
+    // Hand the notification off to the relay worker, if configured. The send
+    // is non-blocking, so a slow or unreachable endpoint never stalls the
+    // COM callback.
+    if let Some(relay) = relay {
+        let _ = relay.send(RelayMessage {
+            app: app_name.clone(),
+            text: text.clone(),
+            timestamp: time.to_rfc3339(),
+        });
+    }
+
+    model.lock().unwrap().push(NotificationRecord {
         app_name,
-        hour,
-        time.minute(),
-        if is_pm { "PM" } else { "AM" },
-        text
-    );
+        text,
+        time,
+        id: notification_id,
+        logo,
+    });
+    post_update();
 
     Ok(())
 }
@@ -186,10 +746,27 @@ async fn get_access() -> Result<()> {
         })
 }
 
-fn setup_listener() -> Result<TokenContainer> {
+fn setup_listener(model: NotificationModel, hwnd_raw: isize) -> Result<TokenContainer> {
+    // Allow short bursts of 20 and refill one token every 500ms thereafter.
+    let rate_limit = Arc::new(Mutex::new(RateLimit::new(20.0, 1.0 / 500.0)));
+    let seen: Arc<Mutex<SeenSet>> = Arc::new(Mutex::new(SeenSet::new()));
+    let logos: LogoCache = Arc::new(Mutex::new(HashMap::new()));
+    let config = Arc::new(Config::load());
+    let (relay, relay_worker) = match config.relay.as_ref().filter(|r| r.is_active()) {
+        Some(relay_config) => {
+            let (sender, worker) = start_relay(relay_config);
+            (Some(sender), Some(worker))
+        }
+        None => (None, None),
+    };
+
     let handler =
         TypedEventHandler::<UserNotificationListener, UserNotificationChangedEventArgs>::new(
-            notification_handler,
+            move |sender, args| {
+                notification_handler(
+                    sender, args, &rate_limit, &seen, &model, &logos, &config, &relay, hwnd_raw,
+                )
+            },
         );
 
     let listener = UserNotificationListener::Current()?;
@@ -198,7 +775,10 @@ fn setup_listener() -> Result<TokenContainer> {
 
     listener
         .NotificationChanged(&handler)
-        .and_then(|token| Ok(TokenContainer { token }))
+        .map(move |token| TokenContainer {
+            token,
+            relay_worker,
+        })
 }
 
 fn error_dialog_and_quit(e: Box<dyn std::error::Error>) {
@@ -214,15 +794,117 @@ fn main() -> Result<()> {
         _ => (),
     }
 
-    let _token = match setup_listener() {
-        Ok(t) => t,
-        Err(e) => {
-            println!("{}", e);
-            panic!();
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rate_limit_spends_its_initial_tokens_then_drops() {
+        // No refill (rate 0), so only the initial `max_tokens` are admitted.
+        let mut bucket = RateLimit::new(3.0, 0.0);
+        assert!(bucket.allow());
+        assert!(bucket.allow());
+        assert!(bucket.allow());
+        assert!(!bucket.allow());
+        assert!(!bucket.allow());
+        assert_eq!(bucket.dropped, 2);
+    }
+
+    #[test]
+    fn rate_limit_refills_over_time_up_to_max() {
+        let mut bucket = RateLimit::new(5.0, 1.0); // one token per millisecond
+        bucket.tokens = 0.0;
+        // Pretend 10ms elapsed; refill should cap at max_tokens, not 10.
+        bucket.last_refill = Instant::now() - Duration::from_millis(10);
+        assert!(bucket.allow());
+        assert!(bucket.tokens <= 5.0);
+    }
+
+    #[test]
+    fn seen_set_dedups_and_forgets_on_remove() {
+        let mut seen = SeenSet::new();
+        assert!(!seen.contains(7));
+        seen.insert(7);
+        assert!(seen.contains(7));
+        seen.insert(7); // idempotent
+        seen.remove(7);
+        assert!(!seen.contains(7));
+    }
+
+    fn config_with(app: &str, rule: AppRule) -> Config {
+        let mut apps = HashMap::new();
+        apps.insert(app.to_owned(), rule);
+        Config {
+            enabled: true,
+            apps,
+            relay: None,
         }
-    };
+    }
 
-    println!("Listener registered");
+    #[test]
+    fn should_surface_respects_global_toggle() {
+        let config = Config {
+            enabled: false,
+            ..Config::default()
+        };
+        assert!(!config.should_surface("Anything", "hi"));
+    }
 
-    Ok(())
+    #[test]
+    fn should_surface_allows_unlisted_apps() {
+        assert!(Config::default().should_surface("Mail", "you have mail"));
+    }
+
+    #[test]
+    fn should_surface_blocks_blocked_apps() {
+        let config = config_with(
+            "Spam",
+            AppRule {
+                action: AppAction::Block,
+                ..AppRule::default()
+            },
+        );
+        assert!(!config.should_surface("Spam", "buy now"));
+    }
+
+    #[test]
+    fn should_surface_drops_on_keyword_case_insensitively() {
+        let config = config_with(
+            "Chat",
+            AppRule {
+                keywords: vec!["muted".to_owned()],
+                ..AppRule::default()
+            },
+        );
+        assert!(!config.should_surface("Chat", "This thread is MUTED"));
+        assert!(config.should_surface("Chat", "hello there"));
+    }
+
+    #[test]
+    fn should_surface_drops_on_compiled_regex() {
+        let mut config = config_with(
+            "Build",
+            AppRule {
+                regex: Some(r"(?i)build \d+ failed".to_owned()),
+                ..AppRule::default()
+            },
+        );
+        config.compile_regexes();
+        assert!(!config.should_surface("Build", "Build 42 failed"));
+        assert!(config.should_surface("Build", "Build 42 succeeded"));
+    }
+
+    #[test]
+    fn seen_set_evicts_oldest_past_capacity() {
+        let mut seen = SeenSet::new();
+        for id in 0..(SEEN_CAPACITY as u32 + 1) {
+            seen.insert(id);
+        }
+        // The very first ID should have been evicted, the latest retained.
+        assert!(!seen.contains(0));
+        assert!(seen.contains(SEEN_CAPACITY as u32));
+    }
 }